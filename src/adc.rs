@@ -0,0 +1,148 @@
+//! One-shot ADC1 driver for single-conversion reads.
+//!
+//! [`init`] enables the ADC clock, selects a clock source, brings up the
+//! voltage regulator, runs the self-calibration sequence, and enables the
+//! converter. [`read_channel`] then selects a channel as the sole entry of
+//! the regular sequence, starts a conversion, and polls for
+//! end-of-conversion. [`read_temperature`] and [`read_vrefint`] cover the
+//! two internal channels.
+
+use cortex_m::asm;
+use volatile_register::RW;
+
+const RCC_ADDR: u32 = 0x4002_1000;
+const RCC_AHBENR_OFFSET: u32 = 0x14;
+const RCC_AHBENR: u32 = RCC_ADDR + RCC_AHBENR_OFFSET;
+
+const ADC1_ADDR: u32 = 0x5000_0000;
+const ADC_ISR_OFFSET: u32 = 0x00;
+const ADC_CR_OFFSET: u32 = 0x08;
+const ADC_SMPR1_OFFSET: u32 = 0x14;
+const ADC_SMPR2_OFFSET: u32 = 0x18;
+const ADC_SQR1_OFFSET: u32 = 0x30;
+const ADC_DR_OFFSET: u32 = 0x40;
+
+const ADC_COMMON_ADDR: u32 = 0x5000_0300;
+const ADC_CCR_OFFSET: u32 = 0x08;
+
+/// Internal temperature-sensor channel, shared by both ADC1 and ADC2.
+pub const CHANNEL_TEMP_SENSOR: u8 = 16;
+/// Internal V_REFINT channel.
+pub const CHANNEL_VREFINT: u8 = 18;
+
+/// A sample time long enough for the slow internal channels (temp sensor, V_REFINT).
+pub const SAMPLE_TIME_LONGEST: u8 = 0b111;
+
+/// T_ADCVREG_STUP (the voltage regulator start-up time) is ~10us; this is a generous
+/// margin of core clock cycles assuming the default 8 MHz HSI core clock.
+const ADVREGEN_STARTUP_CYCLES: u32 = 200;
+
+/// Enables the ADC clock, calibrates ADC1, and enables the converter.
+pub fn init() {
+    unsafe {
+        let ahbenr = &*(RCC_AHBENR as *mut RW<u32>);
+        ahbenr.modify(|r| r | (1 << 28)); // ADC12EN
+    }
+
+    select_clock_source();
+    enable_voltage_regulator();
+    calibrate();
+
+    unsafe {
+        let cr = &*((ADC1_ADDR + ADC_CR_OFFSET) as *mut RW<u32>);
+        cr.modify(|r| r | (1 << 0)); // ADEN
+
+        let isr = &*((ADC1_ADDR + ADC_ISR_OFFSET) as *const RW<u32>);
+        while isr.read() & (1 << 0) == 0 {} // Wait for ADRDY
+    }
+}
+
+/// Synchronous clock mode, ADC clock = HCLK/1 (requires the AHB prescaler to be 1,
+/// which is the case at reset).
+fn select_clock_source() {
+    unsafe {
+        let ccr = &*((ADC_COMMON_ADDR + ADC_CCR_OFFSET) as *mut RW<u32>);
+        ccr.modify(|r| (r & !(0b11 << 16)) | (0b01 << 16)); // CKMODE
+    }
+}
+
+/// Brings the ADC voltage regulator out of its reset-time intermediate state and
+/// waits out its start-up time; `ADCAL` cannot self-clear without this.
+fn enable_voltage_regulator() {
+    unsafe {
+        let cr = &*((ADC1_ADDR + ADC_CR_OFFSET) as *mut RW<u32>);
+        cr.modify(|r| (r & !(0b11 << 28)) | (0b01 << 28)); // ADVREGEN = enabled
+    }
+    asm::delay(ADVREGEN_STARTUP_CYCLES);
+}
+
+fn calibrate() {
+    unsafe {
+        let cr = &*((ADC1_ADDR + ADC_CR_OFFSET) as *mut RW<u32>);
+        cr.modify(|r| r | (1 << 31)); // ADCAL
+        while cr.read() & (1 << 31) != 0 {} // ADCAL self-clears when calibration completes
+    }
+}
+
+/// Sets the sample time (0-7, see `SMPx` encoding) for `channel`.
+pub fn set_sample_time(channel: u8, sample_time: u8) {
+    unsafe {
+        if channel < 10 {
+            let smpr1 = &*((ADC1_ADDR + ADC_SMPR1_OFFSET) as *mut RW<u32>);
+            let shift = (channel as u32) * 3;
+            smpr1.modify(|r| (r & !(0b111 << shift)) | ((sample_time as u32) << shift));
+        } else {
+            let smpr2 = &*((ADC1_ADDR + ADC_SMPR2_OFFSET) as *mut RW<u32>);
+            let shift = ((channel - 10) as u32) * 3;
+            smpr2.modify(|r| (r & !(0b111 << shift)) | ((sample_time as u32) << shift));
+        }
+    }
+}
+
+/// Enables the internal temperature sensor, connecting it to its ADC channel.
+pub fn enable_temp_sensor() {
+    unsafe {
+        let ccr = &*((ADC_COMMON_ADDR + ADC_CCR_OFFSET) as *mut RW<u32>);
+        ccr.modify(|r| r | (1 << 23)); // TSEN
+    }
+}
+
+/// Enables the internal voltage reference, connecting it to its ADC channel.
+pub fn enable_vrefint() {
+    unsafe {
+        let ccr = &*((ADC_COMMON_ADDR + ADC_CCR_OFFSET) as *mut RW<u32>);
+        ccr.modify(|r| r | (1 << 22)); // VREFEN
+    }
+}
+
+/// Runs a single conversion on `channel` and returns the 12-bit result.
+pub fn read_channel(channel: u8) -> u16 {
+    unsafe {
+        // Select channel as the only entry (length 1) of the regular sequence
+        let sqr1 = &*((ADC1_ADDR + ADC_SQR1_OFFSET) as *mut RW<u32>);
+        sqr1.modify(|r| (r & !(0b1_1111 << 6)) | ((channel as u32) << 6));
+
+        let cr = &*((ADC1_ADDR + ADC_CR_OFFSET) as *mut RW<u32>);
+        cr.modify(|r| r | (1 << 2)); // ADSTART
+
+        let isr = &*((ADC1_ADDR + ADC_ISR_OFFSET) as *const RW<u32>);
+        while isr.read() & (1 << 2) == 0 {} // Wait for EOC
+
+        let dr = &*((ADC1_ADDR + ADC_DR_OFFSET) as *const RW<u32>);
+        dr.read() as u16
+    }
+}
+
+/// Reads the on-chip temperature sensor.
+pub fn read_temperature() -> u16 {
+    enable_temp_sensor();
+    set_sample_time(CHANNEL_TEMP_SENSOR, SAMPLE_TIME_LONGEST);
+    read_channel(CHANNEL_TEMP_SENSOR)
+}
+
+/// Reads the internal voltage reference (V_REFINT).
+pub fn read_vrefint() -> u16 {
+    enable_vrefint();
+    set_sample_time(CHANNEL_VREFINT, SAMPLE_TIME_LONGEST);
+    read_channel(CHANNEL_VREFINT)
+}
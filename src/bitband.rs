@@ -0,0 +1,35 @@
+//! Atomic single-bit register access via the Cortex-M bit-band alias region.
+//!
+//! Peripherals mapped in 0x4000_0000-0x400F_FFFF are mirrored bit-for-bit at
+//! 0x4200_0000-0x43FF_FFFF: each bit of the peripheral region gets its own
+//! 32-bit word in the alias region, so writing 0 or 1 to that word sets or
+//! clears exactly one bit with no read-modify-write. GPIO (0x4800_0000) is
+//! outside this region and must keep using BSRR instead.
+
+use volatile_register::RW;
+
+const BITBAND_PERIPH_BASE: u32 = 0x4000_0000;
+const BITBAND_PERIPH_END: u32 = 0x400F_FFFF;
+const BITBAND_ALIAS_BASE: u32 = 0x4200_0000;
+
+fn alias_addr(addr: u32, bit: u8) -> u32 {
+    debug_assert!((BITBAND_PERIPH_BASE..=BITBAND_PERIPH_END).contains(&addr));
+    debug_assert!(bit < 32);
+    BITBAND_ALIAS_BASE + (addr - BITBAND_PERIPH_BASE) * 32 + (bit as u32) * 4
+}
+
+/// Atomically sets or clears bit `bit` of the bit-band-region register at `addr`.
+pub fn bb_write(addr: u32, bit: u8, val: bool) {
+    unsafe {
+        let alias = &*(alias_addr(addr, bit) as *mut RW<u32>);
+        alias.write(val as u32);
+    }
+}
+
+/// Atomically reads bit `bit` of the bit-band-region register at `addr`.
+pub fn bb_read(addr: u32, bit: u8) -> bool {
+    unsafe {
+        let alias = &*(alias_addr(addr, bit) as *const RW<u32>);
+        alias.read() != 0
+    }
+}
@@ -0,0 +1,124 @@
+//! Generalised EXTI/NVIC configuration for all 16 external interrupt lines.
+//!
+//! Unlike a hardcoded EXTI0↔PA0 wiring, [`configure_line`] works for any of
+//! the 16 lines and any GPIO port, computing the right `SYSCFG_EXTICRx`
+//! register/nibble and enabling the NVIC vector that line shares with its
+//! neighbours. The single-bit IMR1/RTSR1/FTSR1 set/clear updates go through
+//! [`crate::bitband`] so they're atomic with respect to interrupts; PR1 is
+//! write-1-to-clear and must be written directly instead (see
+//! [`clear_pending`]).
+
+use volatile_register::RW;
+
+use crate::bitband;
+
+const RCC_ADDR: u32 = 0x4002_1000;
+const RCC_APB2ENR_OFFSET: u32 = 0x18;
+const RCC_APB2ENR: u32 = RCC_ADDR + RCC_APB2ENR_OFFSET;
+
+const SYSCFG_ADDR: u32 = 0x4001_0000;
+const SYSCFG_EXTICR1_OFFSET: u32 = 0x08; // EXTICR1 covers lines 0-3; EXTICR2-4 follow at +0x4 each
+
+const EXTI_ADDR: u32 = 0x4001_0400;
+const EXTI_IMR1_OFFSET: u32 = 0x00; // Interrupt mask
+const EXTI_RTSR1_OFFSET: u32 = 0x08; // Rising trigger select
+const EXTI_FTSR1_OFFSET: u32 = 0x0C; // Falling trigger select
+const EXTI_PR1_OFFSET: u32 = 0x14; // Pending
+
+const NVIC_ADDR: u32 = 0xe000_e100;
+const NVIC_ISER0_OFFSET: u32 = 0x00; // Interrupt Set Enable
+
+/// GPIO port to route an EXTI line to, matching the nibble values of `SYSCFG_EXTICRx`.
+#[derive(Clone, Copy)]
+pub enum Port {
+    A = 0,
+    B = 1,
+    C = 2,
+    D = 3,
+    E = 4,
+    F = 5,
+}
+
+/// Which edge(s) of the signal should raise the interrupt.
+#[derive(Clone, Copy)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+/// NVIC interrupt number servicing `line` on the STM32F303 (lines 0-4 each have
+/// a dedicated vector; 5-9 share `EXTI9_5`; 10-15 share `EXTI15_10`).
+fn nvic_irq(line: u8) -> u32 {
+    match line {
+        0 => 6,
+        1 => 7,
+        2 => 8,
+        3 => 9,
+        4 => 10,
+        5..=9 => 23,
+        10..=15 => 40,
+        _ => panic!("invalid EXTI line"),
+    }
+}
+
+fn enable_nvic(line: u8) {
+    let irq = nvic_irq(line);
+    unsafe {
+        let iser = &*((NVIC_ADDR + NVIC_ISER0_OFFSET + (irq / 32) * 4) as *mut RW<u32>);
+        iser.write(1 << (irq % 32)); // Writing 0 to other bits has no effect on NVIC_ISERx
+    }
+}
+
+/// Routes `line` to `port` and arms it for `edge`, enabling the NVIC vector that services it.
+pub fn configure_line(line: u8, port: Port, edge: Edge) {
+    unsafe {
+        // Enable the clock to the SYSCFG peripheral
+        let apb2enr = &*(RCC_APB2ENR as *mut RW<u32>);
+        apb2enr.modify(|r| r | (1 << 0)); // Bit 0 (SYSCFGEN)
+
+        // Four lines per EXTICRx register, four bits per line
+        let exticr_addr = SYSCFG_ADDR + SYSCFG_EXTICR1_OFFSET + ((line / 4) as u32) * 4;
+        let exticr = &*(exticr_addr as *mut RW<u32>);
+        let shift = (line % 4) as u32 * 4;
+        let mask = 0b1111 << shift;
+        exticr.modify(|r| (r & !mask) | ((port as u32) << shift));
+    }
+
+    // Unmask the line
+    bitband::bb_write(EXTI_ADDR + EXTI_IMR1_OFFSET, line, true);
+
+    match edge {
+        Edge::Rising => {
+            bitband::bb_write(EXTI_ADDR + EXTI_RTSR1_OFFSET, line, true);
+            bitband::bb_write(EXTI_ADDR + EXTI_FTSR1_OFFSET, line, false);
+        }
+        Edge::Falling => {
+            bitband::bb_write(EXTI_ADDR + EXTI_FTSR1_OFFSET, line, true);
+            bitband::bb_write(EXTI_ADDR + EXTI_RTSR1_OFFSET, line, false);
+        }
+        Edge::Both => {
+            bitband::bb_write(EXTI_ADDR + EXTI_RTSR1_OFFSET, line, true);
+            bitband::bb_write(EXTI_ADDR + EXTI_FTSR1_OFFSET, line, true);
+        }
+    }
+
+    enable_nvic(line);
+}
+
+/// Returns whether `line` currently has a pending interrupt.
+pub fn pending(line: u8) -> bool {
+    bitband::bb_read(EXTI_ADDR + EXTI_PR1_OFFSET, line)
+}
+
+/// Clears the pending bit for `line`.
+pub fn clear_pending(line: u8) {
+    unsafe {
+        // PR1 is write-1-to-clear. A bit-band alias write reads back the whole word and
+        // writes it out with only the target bit changed, so it would re-write every
+        // other currently-pending bit as 1 too and clear those lines as a side effect.
+        // Write the single-bit mask directly instead.
+        let exti_pr1 = &*((EXTI_ADDR + EXTI_PR1_OFFSET) as *mut RW<u32>);
+        exti_pr1.write(1 << line);
+    }
+}
@@ -0,0 +1,298 @@
+//! Type-state GPIO pin API.
+//!
+//! Each [`Pin`] is parameterised over its port (`P`), pin number (`N`), and
+//! current mode (`Input`, `Output`, or `Alternate`). Transition methods such
+//! as [`Pin::into_push_pull_output`] perform the necessary register writes
+//! and return a `Pin` typed in the new mode, so operations that don't make
+//! sense for the current configuration (e.g. driving an input pin) simply
+//! don't compile.
+
+use core::marker::PhantomData;
+use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin};
+use volatile_register::RW;
+
+// Reset & Clock Control
+const RCC_ADDR: u32 = 0x4002_1000;
+const RCC_AHBENR_OFFSET: u32 = 0x14;
+const RCC_AHBENR: u32 = RCC_ADDR + RCC_AHBENR_OFFSET;
+
+const MODER_OFFSET: u32 = 0x00;
+const PUPDR_OFFSET: u32 = 0x0C;
+const IDR_OFFSET: u32 = 0x10;
+const ODR_OFFSET: u32 = 0x14;
+const BSRR_OFFSET: u32 = 0x18;
+
+/// Marker type for a pin configured as a floating input.
+pub struct Input;
+
+/// Marker type for a pin configured as a push-pull output.
+pub struct Output;
+
+/// Marker type for a pin configured as an alternate function.
+pub struct Alternate;
+
+/// Base address of GPIO port `port` (`'A'..='F'` on the STM32F303, 0x400 apart).
+const fn port_base(port: char) -> u32 {
+    0x4800_0000 + (port as u32 - 'A' as u32) * 0x400
+}
+
+/// Bit position of port `port`'s clock enable in `RCC_AHBENR` (IOPAEN is bit 17, contiguous per port).
+const fn rcc_enable_bit(port: char) -> u32 {
+    17 + (port as u32 - 'A' as u32)
+}
+
+fn enable_port_clock(port: char) {
+    unsafe {
+        let rcc_ahbenr = &*(RCC_AHBENR as *mut RW<u32>);
+        rcc_ahbenr.modify(|r| r | (1 << rcc_enable_bit(port)));
+    }
+}
+
+fn set_pin_moder(port: char, pin: u8, mode_bits: u32) {
+    unsafe {
+        let moder = &*((port_base(port) + MODER_OFFSET) as *mut RW<u32>);
+        let shift = (pin as u32) * 2;
+        let mask = 0b11 << shift;
+        moder.modify(|r| (r & !mask) | (mode_bits << shift));
+    }
+}
+
+fn set_pin_pupdr(port: char, pin: u8, pupdr_bits: u32) {
+    unsafe {
+        let pupdr = &*((port_base(port) + PUPDR_OFFSET) as *mut RW<u32>);
+        let shift = (pin as u32) * 2;
+        let mask = 0b11 << shift;
+        pupdr.modify(|r| (r & !mask) | (pupdr_bits << shift));
+    }
+}
+
+/// A single GPIO pin: port `P`, pin number `N`, currently configured in mode `MODE`.
+pub struct Pin<const P: char, const N: u8, MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+impl<const P: char, const N: u8, MODE> Pin<P, N, MODE> {
+    const fn new() -> Self {
+        Self { _mode: PhantomData }
+    }
+
+    /// Configures this pin as a push-pull output, enabling the port's clock if needed.
+    pub fn into_push_pull_output(self) -> Pin<P, N, Output> {
+        enable_port_clock(P);
+        set_pin_moder(P, N, 0b01);
+        Pin::new()
+    }
+
+    /// Configures this pin as a floating input, enabling the port's clock if needed.
+    ///
+    /// Also clears the pin's `PUPDR` field: some pins (e.g. PA13-PA15, PB4) reset
+    /// with a pull enabled, and without this the pin would not actually be floating.
+    pub fn into_floating_input(self) -> Pin<P, N, Input> {
+        enable_port_clock(P);
+        set_pin_moder(P, N, 0b00);
+        set_pin_pupdr(P, N, 0b00);
+        Pin::new()
+    }
+}
+
+impl<const P: char, const N: u8> OutputPin for Pin<P, N, Output> {
+    type Error = core::convert::Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        unsafe {
+            let bsrr = &*((port_base(P) + BSRR_OFFSET) as *mut RW<u32>);
+            bsrr.write(1 << N); // Lower 16 bits of BSRR set the corresponding pin
+        }
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        unsafe {
+            let bsrr = &*((port_base(P) + BSRR_OFFSET) as *mut RW<u32>);
+            bsrr.write(1 << (16 + N)); // Upper 16 bits of BSRR clear the corresponding pin
+        }
+        Ok(())
+    }
+}
+
+impl<const P: char, const N: u8> StatefulOutputPin for Pin<P, N, Output> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        unsafe {
+            let odr = &*((port_base(P) + ODR_OFFSET) as *const RW<u32>);
+            Ok(odr.read() & (1 << N) != 0)
+        }
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        self.is_set_high().map(|set| !set)
+    }
+}
+
+impl<const P: char, const N: u8> embedded_hal::digital::v2::toggleable::Default
+    for Pin<P, N, Output>
+{
+}
+
+impl<const P: char, const N: u8> InputPin for Pin<P, N, Input> {
+    type Error = core::convert::Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        unsafe {
+            let idr = &*((port_base(P) + IDR_OFFSET) as *const RW<u32>);
+            Ok(idr.read() & (1 << N) != 0)
+        }
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+/// Obtains the handle for port `P`, pin `N` in its reset-time mode.
+///
+/// Intended to be called once per physical pin; calling it again hands out a second
+/// handle to the same pin, which is a logic error but not memory-unsafe.
+pub fn pin<const P: char, const N: u8>() -> Pin<P, N, Input> {
+    Pin::new()
+}
+
+/// Obtains the handle for Port A, pin 0 (the USER pushbutton) in its reset-time mode.
+///
+/// Intended to be called once; calling it again hands out a second handle to the
+/// same physical pin, which is a logic error but not memory-unsafe.
+pub fn pa0() -> Pin<'A', 0, Input> {
+    pin::<'A', 0>()
+}
+
+/// Obtains the handle for Port E, pin 15 (one of the compass LEDs) in its reset-time mode.
+///
+/// Intended to be called once; calling it again hands out a second handle to the
+/// same physical pin, which is a logic error but not memory-unsafe.
+pub fn pe15() -> Pin<'E', 15, Input> {
+    pin::<'E', 15>()
+}
+
+// Type-erased pins, for storing pins of differing port/pin/mode in one collection
+// (e.g. the eight compass LEDs on Port E.8-E.15 as a single `[DynPin; 8]`).
+
+/// Runtime mode of a [`DynPin`], mirroring the static mode markers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DynPinMode {
+    Input,
+    Output,
+}
+
+/// Port, pin number, and current mode of a [`DynPin`], tracked at runtime.
+#[derive(Clone, Copy)]
+struct DynPinId {
+    port: char,
+    pin: u8,
+    mode: DynPinMode,
+}
+
+/// Error returned when a [`DynPin`] operation is attempted in a mode that doesn't support it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPinTypeError;
+
+/// A type-erased GPIO pin: port, pin number, and mode are runtime fields rather than
+/// type parameters, so pins of differing static types can be stored in one collection.
+/// Operations check the current mode and return [`InvalidPinTypeError`] on mismatch.
+pub struct DynPin {
+    id: DynPinId,
+}
+
+impl DynPin {
+    /// Reconfigures this pin to `mode`, updating both `MODER` and the tracked mode.
+    pub fn into_mode(&mut self, mode: DynPinMode) {
+        let mode_bits = match mode {
+            DynPinMode::Output => 0b01,
+            DynPinMode::Input => 0b00,
+        };
+        enable_port_clock(self.id.port);
+        set_pin_moder(self.id.port, self.id.pin, mode_bits);
+        self.id.mode = mode;
+    }
+
+    /// Drives the pin high. Fails if the pin isn't currently configured as an output.
+    pub fn set_high(&mut self) -> Result<(), InvalidPinTypeError> {
+        if self.id.mode != DynPinMode::Output {
+            return Err(InvalidPinTypeError);
+        }
+        unsafe {
+            let bsrr = &*((port_base(self.id.port) + BSRR_OFFSET) as *mut RW<u32>);
+            bsrr.write(1 << self.id.pin);
+        }
+        Ok(())
+    }
+
+    /// Drives the pin low. Fails if the pin isn't currently configured as an output.
+    pub fn set_low(&mut self) -> Result<(), InvalidPinTypeError> {
+        if self.id.mode != DynPinMode::Output {
+            return Err(InvalidPinTypeError);
+        }
+        unsafe {
+            let bsrr = &*((port_base(self.id.port) + BSRR_OFFSET) as *mut RW<u32>);
+            bsrr.write(1 << (16 + self.id.pin));
+        }
+        Ok(())
+    }
+
+    /// Reads the pin state. Fails if the pin isn't currently configured as an input.
+    pub fn is_high(&self) -> Result<bool, InvalidPinTypeError> {
+        if self.id.mode != DynPinMode::Input {
+            return Err(InvalidPinTypeError);
+        }
+        unsafe {
+            let idr = &*((port_base(self.id.port) + IDR_OFFSET) as *const RW<u32>);
+            Ok(idr.read() & (1 << self.id.pin) != 0)
+        }
+    }
+}
+
+impl<const P: char, const N: u8> From<Pin<P, N, Output>> for DynPin {
+    fn from(_pin: Pin<P, N, Output>) -> Self {
+        DynPin {
+            id: DynPinId {
+                port: P,
+                pin: N,
+                mode: DynPinMode::Output,
+            },
+        }
+    }
+}
+
+impl<const P: char, const N: u8> From<Pin<P, N, Input>> for DynPin {
+    fn from(_pin: Pin<P, N, Input>) -> Self {
+        DynPin {
+            id: DynPinId {
+                port: P,
+                pin: N,
+                mode: DynPinMode::Input,
+            },
+        }
+    }
+}
+
+impl<const P: char, const N: u8> TryFrom<DynPin> for Pin<P, N, Output> {
+    type Error = InvalidPinTypeError;
+
+    fn try_from(pin: DynPin) -> Result<Self, Self::Error> {
+        if pin.id.port == P && pin.id.pin == N && pin.id.mode == DynPinMode::Output {
+            Ok(Pin::new())
+        } else {
+            Err(InvalidPinTypeError)
+        }
+    }
+}
+
+impl<const P: char, const N: u8> TryFrom<DynPin> for Pin<P, N, Input> {
+    type Error = InvalidPinTypeError;
+
+    fn try_from(pin: DynPin) -> Result<Self, Self::Error> {
+        if pin.id.port == P && pin.id.pin == N && pin.id.mode == DynPinMode::Input {
+            Ok(Pin::new())
+        } else {
+            Err(InvalidPinTypeError)
+        }
+    }
+}
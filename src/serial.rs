@@ -0,0 +1,195 @@
+//! Buffered USART1 TX, as an alternative to logging over ITM/SWO.
+//!
+//! [`Serial::init`] configures PA9 as `USART1_TX` (AF7) and enables the
+//! peripheral. [`Serial::write_byte`] never blocks on the wire: it pushes
+//! into a [`RingBuffer`] and enables the TXE interrupt, and the USART1 TX
+//! interrupt handler (wired up by the caller) drains the buffer one byte per
+//! TXE event, disabling the interrupt once it's empty. This lets `writeln!`
+//! work over a real UART/FTDI cable with no debug probe attached.
+//!
+//! `write_byte` drops the byte and returns `false` if the ring buffer is
+//! full rather than spinning, since a caller with the USART1 interrupt
+//! masked (e.g. inside a `cortex_m::interrupt::free` critical section)
+//! would otherwise deadlock waiting for the buffer to drain.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use volatile_register::RW;
+
+// Reset & Clock Control
+const RCC_ADDR: u32 = 0x4002_1000;
+const RCC_AHBENR_OFFSET: u32 = 0x14;
+const RCC_AHBENR: u32 = RCC_ADDR + RCC_AHBENR_OFFSET;
+const RCC_APB2ENR_OFFSET: u32 = 0x18;
+const RCC_APB2ENR: u32 = RCC_ADDR + RCC_APB2ENR_OFFSET;
+
+// General Purpose IO Port A (PA9 = USART1_TX, AF7)
+const GPIOA_ADDR: u32 = 0x4800_0000;
+const GPIOA_MODER_ADDR: u32 = GPIOA_ADDR + 0x00;
+const GPIOA_AFRH_ADDR: u32 = GPIOA_ADDR + 0x24; // AF selection for pins 8-15
+
+const USART1_ADDR: u32 = 0x4001_3800;
+const USART_CR1_OFFSET: u32 = 0x00;
+const USART_BRR_OFFSET: u32 = 0x0C;
+const USART_TDR_OFFSET: u32 = 0x28;
+
+/// USART1 is clocked from the 8 MHz HSI by default.
+const USART_CLOCK_HZ: u32 = 8_000_000;
+
+// Nested Vector Interrupt Controller
+const NVIC_ADDR: u32 = 0xe000_e100;
+const NVIC_ISER1_OFFSET: u32 = 0x04; // Interrupt Set Enable for IRQs 32-63
+const USART1_IRQ: u32 = 37; // USART1_EXTI25
+
+/// A lock-free single-producer/single-consumer byte ring buffer.
+///
+/// `N` must be a power of two so the head/tail indices can wrap with a mask
+/// instead of a division. The producer calls [`push`](RingBuffer::push) and
+/// the consumer calls [`pop`](RingBuffer::pop); both take `&self` and rely on
+/// atomics rather than a lock.
+pub struct RingBuffer<const N: usize> {
+    buf: UnsafeCell<[u8; N]>,
+    head: AtomicUsize, // next index to write
+    tail: AtomicUsize, // next index to read
+}
+
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    const ASSERT_POWER_OF_TWO: () = assert!(N.is_power_of_two());
+    const MASK: usize = N - 1;
+
+    pub const fn new() -> Self {
+        let _ = Self::ASSERT_POWER_OF_TWO;
+        Self {
+            buf: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+
+    pub fn is_full(&self) -> bool {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        head.wrapping_sub(tail) == N
+    }
+
+    /// Pushes a byte. Returns `false` without writing if the buffer is full.
+    pub fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) == N {
+            return false;
+        }
+        unsafe {
+            (*self.buf.get())[head & Self::MASK] = byte;
+        }
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Pops the oldest byte, or `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let byte = unsafe { (*self.buf.get())[tail & Self::MASK] };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Some(byte)
+    }
+}
+
+/// A buffered handle to USART1, transmit-only.
+pub struct Serial {
+    tx: RingBuffer<64>,
+}
+
+impl Serial {
+    pub const fn new() -> Self {
+        Self {
+            tx: RingBuffer::new(),
+        }
+    }
+
+    /// Enables PA9 (USART1_TX) and the USART1 peripheral at `baud`.
+    pub fn init(&self, baud: u32) {
+        unsafe {
+            let ahbenr = &*(RCC_AHBENR as *mut RW<u32>);
+            ahbenr.modify(|r| r | (1 << 17)); // Bit 17 is the I/O port A clock enable
+
+            let apb2enr = &*(RCC_APB2ENR as *mut RW<u32>);
+            apb2enr.modify(|r| r | (1 << 14)); // USART1EN
+
+            // PA9 into alternate function mode, function AF7 (USART1_TX)
+            let moder = &*(GPIOA_MODER_ADDR as *mut RW<u32>);
+            moder.modify(|r| (r & !(0b11 << 18)) | (0b10 << 18));
+
+            let afrh = &*(GPIOA_AFRH_ADDR as *mut RW<u32>);
+            afrh.modify(|r| (r & !(0b1111 << 4)) | (0b0111 << 4));
+
+            let brr = &*((USART1_ADDR + USART_BRR_OFFSET) as *mut RW<u32>);
+            brr.write(USART_CLOCK_HZ / baud);
+
+            let cr1 = &*((USART1_ADDR + USART_CR1_OFFSET) as *mut RW<u32>);
+            cr1.modify(|r| r | (1 << 3) | (1 << 0)); // TE, UE
+
+            // Unmask the USART1 interrupt in the NVIC, or TXE never fires, the ring
+            // buffer never drains, and write_byte drops every byte once it fills up
+            let iser1 = &*((NVIC_ADDR + NVIC_ISER1_OFFSET) as *mut RW<u32>);
+            iser1.write(1 << (USART1_IRQ - 32)); // Writing 0 to other bits has no effect on NVIC_ISERx
+        }
+    }
+
+    /// Queues `byte` for transmission, enabling the TXE interrupt to drain it.
+    /// Returns `false` without queuing the byte if the ring buffer is full.
+    pub fn write_byte(&self, byte: u8) -> bool {
+        if !self.tx.push(byte) {
+            return false;
+        }
+        unsafe {
+            let cr1 = &*((USART1_ADDR + USART_CR1_OFFSET) as *mut RW<u32>);
+            cr1.modify(|r| r | (1 << 7)); // TXEIE
+        }
+        true
+    }
+
+    /// Called from the USART1 TX interrupt handler: sends the next buffered byte,
+    /// or disables the TXE interrupt if the buffer has drained.
+    pub fn on_tx_empty(&self) {
+        match self.tx.pop() {
+            Some(byte) => unsafe {
+                let tdr = &*((USART1_ADDR + USART_TDR_OFFSET) as *mut RW<u32>);
+                tdr.write(byte as u32);
+            },
+            None => unsafe {
+                let cr1 = &*((USART1_ADDR + USART_CR1_OFFSET) as *mut RW<u32>);
+                cr1.modify(|r| r & !(1 << 7)); // TXEIE
+            },
+        }
+    }
+
+    /// Returns a [`core::fmt::Write`] adapter so `writeln!(serial.writer(), ...)` works.
+    pub fn writer(&self) -> SerialWriter<'_> {
+        SerialWriter(self)
+    }
+}
+
+/// Adapter implementing [`core::fmt::Write`] over a shared [`Serial`] reference.
+pub struct SerialWriter<'a>(&'a Serial);
+
+impl core::fmt::Write for SerialWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // Best-effort: bytes that don't fit while the buffer is full are dropped
+        // rather than blocking (see the module docs).
+        for byte in s.bytes() {
+            self.0.write_byte(byte);
+        }
+        Ok(())
+    }
+}